@@ -6,42 +6,257 @@ use std::collections::HashMap;
 use super::{Encoding, StreamOffsets};
 
 use peniko::{
-    kurbo::{BezPath, Shape},
+    kurbo::{BezPath, PathEl, Shape},
     Fill, Style,
 };
-use skrifa::{instance::NormalizedCoord, outline::OutlinePen, GlyphId, OutlineGlyphCollection};
+use skrifa::{
+    instance::{NormalizedCoord, Size},
+    outline::{DrawSettings, HintingInstance, HintingMode, OutlinePen},
+    GlyphId, OutlineGlyphCollection,
+};
 
 #[derive(Copy, Clone, PartialEq, Eq, Hash, Default, Debug)]
 pub struct GlyphKey {
     pub font_id: u64,
     pub font_index: u32,
     pub glyph_id: u32,
-    pub font_size_bits: u32,
     pub hint: bool,
 }
 
+/// A source of glyph outlines. The default source is skrifa's [`OutlineGlyphCollection`]
+/// ([`SkrifaOutlines`]), but the trait lets a glyph's path come from elsewhere — most notably a
+/// sandboxed WASM module that returns contours directly ([`WasmOutlines`]), analogous to the
+/// HarfBuzz WASM shaper's `font_copy_glyph_outline` hook.
+pub trait OutlineSource {
+    /// Draws the outline for `glyph_id` at `size` with the given variation `coords`, emitting its
+    /// contours to `pen`. Returns `None` if the glyph has no outline.
+    fn draw(
+        &self,
+        glyph_id: u32,
+        size: Size,
+        coords: &[NormalizedCoord],
+        pen: &mut dyn OutlinePen,
+    ) -> Option<()>;
+
+    /// Returns the backing skrifa collection when the source is a real font, enabling grid-fit
+    /// hinting. Procedural sources return `None`, which transparently disables hinting for them.
+    fn outlines(&self) -> Option<&OutlineGlyphCollection<'_>> {
+        None
+    }
+}
+
+/// The default [`OutlineSource`], backed by skrifa's [`OutlineGlyphCollection`].
+pub struct SkrifaOutlines<'a> {
+    pub outlines: &'a OutlineGlyphCollection<'a>,
+}
+
+impl<'a> SkrifaOutlines<'a> {
+    pub fn new(outlines: &'a OutlineGlyphCollection<'a>) -> Self {
+        Self { outlines }
+    }
+}
+
+impl OutlineSource for SkrifaOutlines<'_> {
+    fn draw(
+        &self,
+        glyph_id: u32,
+        size: Size,
+        coords: &[NormalizedCoord],
+        pen: &mut dyn OutlinePen,
+    ) -> Option<()> {
+        let outline = self.outlines.get(GlyphId::new(glyph_id as u16))?;
+        let draw_settings = DrawSettings::unhinted(size, coords);
+        outline.draw(draw_settings, &mut PenRef(pen)).ok()
+    }
+
+    fn outlines(&self) -> Option<&OutlineGlyphCollection<'_>> {
+        Some(self.outlines)
+    }
+}
+
+/// A sandboxed module that returns a glyph outline as a kurbo [`BezPath`] scaled to the requested
+/// size (font units for [`Size::unscaled`]), mirroring the HarfBuzz WASM shaper's
+/// `font_copy_glyph_outline` hook.
+pub trait WasmOutlineModule {
+    /// Returns the outline of `glyph_id` at `size` with variation `coords`, or `None` if the
+    /// module declines to produce one.
+    fn glyph_outline(
+        &self,
+        glyph_id: u32,
+        size: Size,
+        coords: &[NormalizedCoord],
+    ) -> Option<BezPath>;
+}
+
+/// An [`OutlineSource`] backed by a WASM module. The module's returned bezier contours are
+/// replayed through the same [`OutlinePen`] path the skrifa source uses, so procedural or
+/// programmatically-patched fonts flow through the existing encoder unchanged.
+pub struct WasmOutlines<M>(pub M);
+
+impl<M: WasmOutlineModule> OutlineSource for WasmOutlines<M> {
+    fn draw(
+        &self,
+        glyph_id: u32,
+        size: Size,
+        coords: &[NormalizedCoord],
+        pen: &mut dyn OutlinePen,
+    ) -> Option<()> {
+        let path = self.0.glyph_outline(glyph_id, size, coords)?;
+        for el in path.elements() {
+            match *el {
+                PathEl::MoveTo(p) => pen.move_to(p.x as f32, p.y as f32),
+                PathEl::LineTo(p) => pen.line_to(p.x as f32, p.y as f32),
+                PathEl::QuadTo(c, p) => {
+                    pen.quad_to(c.x as f32, c.y as f32, p.x as f32, p.y as f32);
+                }
+                PathEl::CurveTo(c0, c1, p) => pen.curve_to(
+                    c0.x as f32,
+                    c0.y as f32,
+                    c1.x as f32,
+                    c1.y as f32,
+                    p.x as f32,
+                    p.y as f32,
+                ),
+                PathEl::ClosePath => pen.close(),
+            }
+        }
+        Some(())
+    }
+}
+
+/// Adapts a `&mut dyn OutlinePen` into a concrete pen so it can be handed to skrifa's generic
+/// `draw`, which requires a sized pen.
+struct PenRef<'a>(&'a mut dyn OutlinePen);
+
+impl OutlinePen for PenRef<'_> {
+    fn move_to(&mut self, x: f32, y: f32) {
+        self.0.move_to(x, y);
+    }
+
+    fn line_to(&mut self, x: f32, y: f32) {
+        self.0.line_to(x, y);
+    }
+
+    fn quad_to(&mut self, cx0: f32, cy0: f32, x: f32, y: f32) {
+        self.0.quad_to(cx0, cy0, x, y);
+    }
+
+    fn curve_to(&mut self, cx0: f32, cy0: f32, cx1: f32, cy1: f32, x: f32, y: f32) {
+        self.0.curve_to(cx0, cy0, cx1, cy1, x, y);
+    }
+
+    fn close(&mut self) {
+        self.0.close();
+    }
+}
+
+/// Controls whether variable-font glyphs are cached, and how coarsely their normalized
+/// coordinates are snapped before being used as a cache key.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum CoordQuantize {
+    /// Snap each [`NormalizedCoord`] to the nearest multiple of this step, expressed in 2.14
+    /// fixed-point units, before building the key. A step of 256 is `1/64` in normalized space.
+    ///
+    /// Animations that settle on or near a named instance then hit the cache instead of
+    /// re-encoding every frame.
+    Snap(u16),
+    /// Never cache variable glyphs; re-encode them on every call. Appropriate for callers
+    /// rendering continuous morphs where no two frames share coordinates.
+    Off,
+}
+
+impl Default for CoordQuantize {
+    fn default() -> Self {
+        // `1/64` of normalized space: coarse enough to coalesce settled animations, fine enough
+        // that the snapped outline is visually indistinguishable from the requested one.
+        Self::Snap(256)
+    }
+}
+
 #[derive(Default)]
 pub struct GlyphCache {
     pub encoding: Encoding,
     glyphs: HashMap<GlyphKey, CachedRange>,
+    var_glyphs: HashMap<VarGlyphKey, CachedRange>,
+    // Hinted outlines are size-specific, so they can't share the per-em `glyphs` map; they cache
+    // against the pixel size instead.
+    hinted_glyphs: HashMap<(GlyphKey, u32), CachedRange>,
+    hints: HintCache,
+    quantize: CoordQuantize,
 }
 
 impl GlyphCache {
     pub fn clear(&mut self) {
         self.encoding.reset();
         self.glyphs.clear();
+        self.var_glyphs.clear();
+        self.hinted_glyphs.clear();
+        self.hints.entries.clear();
+    }
+
+    /// Sets how variable-font glyph coordinates are quantized for caching. See [`CoordQuantize`].
+    pub fn set_coord_quantize(&mut self, quantize: CoordQuantize) {
+        self.quantize = quantize;
     }
 
     pub fn get_or_insert(
         &mut self,
-        outlines: &OutlineGlyphCollection,
+        source: &impl OutlineSource,
         key: GlyphKey,
         style: &Style,
         font_size: f32,
         coords: &[NormalizedCoord],
     ) -> Option<CachedRange> {
-        let size = skrifa::instance::Size::new(font_size);
+        let size = Size::new(font_size);
         let is_var = !coords.is_empty();
+        // Non-hinted, non-zero filled glyphs are encoded once at the font's native per-em scale and
+        // reused across every pixel size. The consuming glyph-run encoder prepends a per-glyph
+        // `font_size / units_per_em` scale transform when it splices the cached range into the
+        // stream. Hinted, stroked and even-odd filled outlines keep encoding at the requested
+        // `size`.
+        let normalize = matches!(style, Style::Fill(Fill::NonZero)) && !key.hint;
+        // Hinting depends on the size and variation coordinates but not the glyph id, so a single
+        // instance is shared across every glyph of a run. Building one is relatively cheap, so the
+        // cache only keeps a handful of the most recently used instances alive. Only real font
+        // sources (those that expose a skrifa collection) can be hinted.
+        let collection = source.outlines();
+        let hinting = if key.hint {
+            collection.and_then(|outlines| {
+                self.hints
+                    .get(font_size.to_bits(), &key, outlines, size, coords)
+            })
+        } else {
+            None
+        };
+        let glyph_size = if normalize { Size::unscaled() } else { size };
+        // When a variable glyph is cached under quantized coordinates, draw it with those same
+        // snapped coordinates so the stored geometry matches its key rather than depending on
+        // whichever frame populated the slot first. Non-cached or non-variable glyphs draw with
+        // the raw coords.
+        let snapped_coords: Option<Box<[NormalizedCoord]>> =
+            match (is_var && matches!(style, Style::Fill(Fill::NonZero)) && !key.hint, self.quantize)
+            {
+                (true, CoordQuantize::Snap(step)) => Some(
+                    quantize_coords(coords, step)
+                        .iter()
+                        .map(|&bits| NormalizedCoord::from_bits(bits))
+                        .collect(),
+                ),
+                _ => None,
+            };
+        let draw_coords = snapped_coords.as_deref().unwrap_or(coords);
+        // Emits the glyph's outline to `pen`: through the cached hinting instance when hinting is
+        // active (a skrifa fast path), otherwise through the pluggable source.
+        let draw_outline = |pen: &mut dyn OutlinePen| -> Option<()> {
+            if let (Some(hinting), Some(outlines)) = (hinting, collection) {
+                let outline = outlines.get(GlyphId::new(key.glyph_id as u16))?;
+                outline
+                    .draw(DrawSettings::hinted(hinting, false), &mut PenRef(pen))
+                    .ok()
+            } else {
+                source.draw(key.glyph_id, glyph_size, draw_coords, pen)
+            }
+        };
         let encoding_cache = &mut self.encoding;
         let mut encode_glyph = || {
             let start = encoding_cache.stream_offsets();
@@ -51,41 +266,71 @@ impl GlyphCache {
             };
             encoding_cache.encode_fill_style(fill);
             let mut path = encoding_cache.encode_path(true);
-            let outline = outlines.get(GlyphId::new(key.glyph_id as u16))?;
-            // FIXME: Re-add hinting when skrifa supports it
-            // Tracking issue <https://github.com/googlefonts/fontations/issues/620>
-            let draw_settings = skrifa::outline::DrawSettings::unhinted(size, coords);
             match style {
                 Style::Fill(_) => {
-                    outline.draw(draw_settings, &mut path).ok()?;
+                    draw_outline(&mut path)?;
                 }
                 Style::Stroke(stroke) => {
                     const STROKE_TOLERANCE: f64 = 0.01;
-                    let mut pen = BezPathPen::default();
-                    outline.draw(draw_settings, &mut pen).ok()?;
-                    let stroked = peniko::kurbo::stroke(
-                        pen.0.path_elements(STROKE_TOLERANCE),
-                        stroke,
-                        &Default::default(),
-                        STROKE_TOLERANCE,
-                    );
-                    path.shape(&stroked);
+                    // Stroke and encode one contour at a time rather than materializing the whole
+                    // glyph. The `BezPath` buffer is reused across contours, so the temporary
+                    // allocation is bounded to a single subpath.
+                    let mut pen = StreamStrokePen::new(|contour: &BezPath| {
+                        let stroked = peniko::kurbo::stroke(
+                            contour.path_elements(STROKE_TOLERANCE),
+                            stroke,
+                            &Default::default(),
+                            STROKE_TOLERANCE,
+                        );
+                        path.shape(&stroked);
+                    });
+                    draw_outline(&mut pen)?;
+                    // Flush the final contour, which an outline may leave open (no `close`).
+                    pen.finish();
                 }
             }
             if path.finish(false) == 0 {
                 return None;
             }
             let end = encoding_cache.stream_offsets();
-            Some(CachedRange { start, end })
+            Some(CachedRange {
+                start,
+                end,
+                normalized: normalize,
+            })
         };
-        // For now, only cache non-zero filled, non-variable glyphs so we don't need to keep style
-        // as part of the key.
-        let range = if matches!(style, Style::Fill(Fill::NonZero)) && !is_var {
-            use std::collections::hash_map::Entry;
+        // We only cache non-zero filled glyphs so we don't need to keep style as part of the key.
+        // Per-em (non-hinted) glyphs share an entry across every pixel size: non-variable ones key
+        // off `GlyphKey` directly, variable ones fold their quantized coordinates into an owned
+        // key and skip the cache when quantization is disabled. Hinted non-variable glyphs are
+        // size-specific, so they cache against `(GlyphKey, font_size_bits)` in a separate map;
+        // hinted variable glyphs are not cached and are re-encoded on every call.
+        use std::collections::hash_map::Entry;
+        let fill_nonzero = matches!(style, Style::Fill(Fill::NonZero));
+        let range = if fill_nonzero && !key.hint && !is_var {
             match self.glyphs.entry(key) {
                 Entry::Occupied(entry) => *entry.get(),
                 Entry::Vacant(entry) => *entry.insert(encode_glyph()?),
             }
+        } else if fill_nonzero && !key.hint && is_var {
+            match self.quantize {
+                CoordQuantize::Snap(step) => {
+                    let var_key = VarGlyphKey {
+                        key,
+                        coords: quantize_coords(coords, step),
+                    };
+                    match self.var_glyphs.entry(var_key) {
+                        Entry::Occupied(entry) => *entry.get(),
+                        Entry::Vacant(entry) => *entry.insert(encode_glyph()?),
+                    }
+                }
+                CoordQuantize::Off => encode_glyph()?,
+            }
+        } else if fill_nonzero && key.hint && !is_var {
+            match self.hinted_glyphs.entry((key, font_size.to_bits())) {
+                Entry::Occupied(entry) => *entry.get(),
+                Entry::Vacant(entry) => *entry.insert(encode_glyph()?),
+            }
         } else {
             encode_glyph()?
         };
@@ -93,10 +338,33 @@ impl GlyphCache {
     }
 }
 
+/// An owned cache key for variable-font glyphs: the base [`GlyphKey`] plus the glyph's normalized
+/// coordinates snapped to the quantization grid.
+#[derive(Clone, PartialEq, Eq, Hash)]
+struct VarGlyphKey {
+    key: GlyphKey,
+    coords: Box<[i16]>,
+}
+
+/// Snaps each normalized coordinate to the nearest multiple of `step` (in 2.14 fixed-point units),
+/// yielding a compact key that coalesces nearby variation positions.
+fn quantize_coords(coords: &[NormalizedCoord], step: u16) -> Box<[i16]> {
+    let step = step.max(1) as f32;
+    coords
+        .iter()
+        .map(|coord| ((coord.to_bits() as f32 / step).round() * step) as i16)
+        .collect()
+}
+
 #[derive(Copy, Clone, Default, Debug)]
 pub struct CachedRange {
     pub start: StreamOffsets,
     pub end: StreamOffsets,
+    /// Whether the range was encoded at the font's per-em scale. When `true`, the consumer must
+    /// prepend a `font_size / units_per_em` scale transform before splicing it in; when `false`
+    /// (hinted, stroked or even-odd filled glyphs) the geometry is already at pixel scale and
+    /// must not be scaled.
+    pub normalized: bool,
 }
 
 impl CachedRange {
@@ -112,26 +380,130 @@ impl CachedRange {
     }
 }
 
-// A wrapper newtype so we can implement the `OutlinePen` trait.
+/// The number of hinting instances we keep around. Instances are cheap to rebuild, so a small
+/// linear-scan LRU is plenty to cover the handful of sizes and variations in flight at once.
+const MAX_CACHED_HINT_INSTANCES: usize = 8;
+
+/// A cache of [`HintingInstance`]s keyed by everything that affects hinting except the glyph id,
+/// so the instance can be reused across every glyph of a run.
 #[derive(Default)]
-struct BezPathPen(BezPath);
+struct HintCache {
+    entries: Vec<HintEntry>,
+    serial: u64,
+}
+
+#[derive(PartialEq, Eq)]
+struct HintKey {
+    font_id: u64,
+    font_index: u32,
+    font_size_bits: u32,
+    coords: Box<[NormalizedCoord]>,
+}
+
+struct HintEntry {
+    key: HintKey,
+    instance: HintingInstance,
+    serial: u64,
+}
+
+impl HintCache {
+    fn get(
+        &mut self,
+        font_size_bits: u32,
+        key: &GlyphKey,
+        outlines: &OutlineGlyphCollection,
+        size: Size,
+        coords: &[NormalizedCoord],
+    ) -> Option<&HintingInstance> {
+        let hint_key = HintKey {
+            font_id: key.font_id,
+            font_index: key.font_index,
+            font_size_bits,
+            coords: coords.into(),
+        };
+        self.serial += 1;
+        let serial = self.serial;
+        let index = if let Some(index) = self.entries.iter().position(|e| e.key == hint_key) {
+            self.entries[index].serial = serial;
+            index
+        } else {
+            let instance =
+                HintingInstance::new(outlines, size, coords, HintingMode::Strong).ok()?;
+            let entry = HintEntry {
+                key: hint_key,
+                instance,
+                serial,
+            };
+            if self.entries.len() < MAX_CACHED_HINT_INSTANCES {
+                self.entries.push(entry);
+                self.entries.len() - 1
+            } else {
+                // Evict the least recently used instance.
+                let lru = self
+                    .entries
+                    .iter()
+                    .enumerate()
+                    .min_by_key(|(_, e)| e.serial)
+                    .map(|(i, _)| i)?;
+                self.entries[lru] = entry;
+                lru
+            }
+        };
+        Some(&self.entries[index].instance)
+    }
+}
+
+/// A pen that strokes and flushes one contour at a time. Elements accumulate into `contour` until
+/// a `close` or a `move_to` that begins a new subpath, at which point the contour is handed to
+/// `flush` (which strokes and encodes it) and the buffer is cleared for reuse. Open contours are
+/// flushed without a closing segment so kurbo applies caps; closed contours retain their
+/// `ClosePath` so kurbo applies joins around the closure.
+struct StreamStrokePen<F: FnMut(&BezPath)> {
+    contour: BezPath,
+    flush: F,
+}
+
+impl<F: FnMut(&BezPath)> StreamStrokePen<F> {
+    fn new(flush: F) -> Self {
+        Self {
+            contour: BezPath::new(),
+            flush,
+        }
+    }
+
+    /// Strokes and encodes the buffered contour, then clears the buffer while retaining its
+    /// allocation.
+    fn flush_contour(&mut self) {
+        if !self.contour.elements().is_empty() {
+            (self.flush)(&self.contour);
+            self.contour.truncate(0);
+        }
+    }
+
+    /// Flushes any trailing contour the outline left open.
+    fn finish(&mut self) {
+        self.flush_contour();
+    }
+}
 
-impl OutlinePen for BezPathPen {
+impl<F: FnMut(&BezPath)> OutlinePen for StreamStrokePen<F> {
     fn move_to(&mut self, x: f32, y: f32) {
-        self.0.move_to((x as f64, y as f64));
+        // A new contour begins: emit the previous (open) one before starting this.
+        self.flush_contour();
+        self.contour.move_to((x as f64, y as f64));
     }
 
     fn line_to(&mut self, x: f32, y: f32) {
-        self.0.line_to((x as f64, y as f64));
+        self.contour.line_to((x as f64, y as f64));
     }
 
     fn quad_to(&mut self, cx0: f32, cy0: f32, x: f32, y: f32) {
-        self.0
+        self.contour
             .quad_to((cx0 as f64, cy0 as f64), (x as f64, y as f64));
     }
 
     fn curve_to(&mut self, cx0: f32, cy0: f32, cx1: f32, cy1: f32, x: f32, y: f32) {
-        self.0.curve_to(
+        self.contour.curve_to(
             (cx0 as f64, cy0 as f64),
             (cx1 as f64, cy1 as f64),
             (x as f64, y as f64),
@@ -139,6 +511,7 @@ impl OutlinePen for BezPathPen {
     }
 
     fn close(&mut self) {
-        self.0.close_path();
+        self.contour.close_path();
+        self.flush_contour();
     }
 }